@@ -1,65 +1,264 @@
 #[cfg(test)]
 mod tests {
-    use numext_fixed_uint::U256;
+    use lazy_static::lazy_static;
     use rand::Rng;
     use ring::{digest, hmac};
-    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+    use ripemd160::{Digest as _, Ripemd160};
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey, SignOnly, VerifyOnly};
+    use std::fmt;
+    use std::str::FromStr;
+
+    lazy_static! {
+        // Building a full `Secp256k1::new()` context randomizes and
+        // allocates on every call, which is wasteful when walking a deep
+        // derivation path or generating many addresses. These are built
+        // once and reused for the narrower operations derivation needs.
+        static ref SECP256K1_SIGNING_ONLY: Secp256k1<SignOnly> = Secp256k1::signing_only();
+        static ref SECP256K1_VERIFICATION_ONLY: Secp256k1<VerifyOnly> = Secp256k1::verification_only();
+    }
 
     type ChainCode = Vec<u8>;
-    const HARDENDED_KEY_START_INDEX: u64 = 2_147_483_648; // 2 ** 31
-    const HARDENDED_KEY_END_INDEX: u64 = 4_294_967_295; // 2 ** 32 - 1
+    const HARDENED_BIT: u32 = 1 << 31; // 2 ** 31
+
+    // BIP32 extended-key version bytes, see
+    // https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#serialization-format
+    const VERSION_MAINNET_PRIVATE: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+    const VERSION_MAINNET_PUBLIC: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+    const VERSION_TESTNET_PRIVATE: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+    const VERSION_TESTNET_PUBLIC: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    enum Network {
+        Bitcoin,
+        Testnet,
+    }
 
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct ExtendedPrivKey {
         private_key: SecretKey,
         chain_code: ChainCode,
+        network: Network,
+        depth: u8,
+        parent_fingerprint: [u8; 4],
+        child_number: u32,
     }
 
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct ExtendedPubKey {
         public_key: PublicKey,
         chain_code: ChainCode,
+        network: Network,
+        depth: u8,
+        parent_fingerprint: [u8; 4],
+        child_number: u32,
     }
 
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
     enum Error {
         IndexOutOfRange,
         InvalidIndex,
-        InvalidKeyMode,
+        InvalidBase58Character,
+        InvalidBase58Checksum,
+        InvalidExtendedKeyLength,
+        InvalidVersionBytes,
+        InvalidPrivateKeyPrefix,
+        InvalidDerivationPath,
+        CannotDeriveHardenedPublicKey,
+        MaximumDepthExceeded,
     }
 
+    // Replaces a separate `KeyMode` + raw index pair: the hardened bit and
+    // the index it applies to can no longer disagree.
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-    enum KeyMode {
-        Normal,
-        Hardened,
+    enum KeyIndex {
+        Normal(u32),
+        Hardened(u32),
     }
 
-    impl From<u64> for KeyMode {
-        fn from(index: u64) -> Self {
-            if index < HARDENDED_KEY_START_INDEX {
-                KeyMode::Normal
-            } else if index <= HARDENDED_KEY_END_INDEX {
-                KeyMode::Hardened
+    impl KeyIndex {
+        /// The on-wire index, with the hardened bit folded back in. Errs if
+        /// the local index is already `>= HARDENED_BIT`, since a `Normal`
+        /// index that large would otherwise collide with the hardened
+        /// range while still deriving as a normal (public-point) child.
+        fn raw_index(&self) -> Result<u32, Error> {
+            match self {
+                KeyIndex::Normal(i) | KeyIndex::Hardened(i) if *i >= HARDENED_BIT => {
+                    Err(Error::IndexOutOfRange)
+                }
+                KeyIndex::Normal(i) => Ok(*i),
+                KeyIndex::Hardened(i) => Ok(HARDENED_BIT + i),
+            }
+        }
+
+        /// The index without the hardened offset.
+        fn normalize_index(&self) -> u32 {
+            match self {
+                KeyIndex::Normal(i) | KeyIndex::Hardened(i) => *i,
+            }
+        }
+
+        /// Splits an on-wire index back into its hardened flag and local
+        /// index.
+        fn from_index(index: u32) -> Result<Self, Error> {
+            if index < HARDENED_BIT {
+                Ok(KeyIndex::Normal(index))
             } else {
-                panic!("Out of range index {:?}", index);
+                Ok(KeyIndex::Hardened(index - HARDENED_BIT))
             }
         }
     }
 
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct ChildPrivKey {
-        index: u64,
-        key_mode: KeyMode,
+        key_index: KeyIndex,
         extended_key: ExtendedPrivKey,
     }
 
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct ChildPubKey {
-        index: u64,
-        key_mode: KeyMode,
+        key_index: KeyIndex,
         extended_key: ExtendedPubKey,
     }
 
+    // An ordered list of child indices, e.g. `m/44'/0'/0'/0/5`, following
+    // rust-bitcoin's `DerivationPath`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct DerivationPath(Vec<KeyIndex>);
+
+    impl FromStr for DerivationPath {
+        type Err = Error;
+
+        fn from_str(s: &str) -> Result<Self, Error> {
+            let mut parts = s.split('/');
+            match parts.next() {
+                Some("m") | Some("M") => {}
+                _ => return Err(Error::InvalidDerivationPath),
+            }
+            let mut path = Vec::new();
+            for part in parts {
+                if part.is_empty() {
+                    return Err(Error::InvalidDerivationPath);
+                }
+                let hardened = part.ends_with('\'') || part.ends_with('h');
+                let digits = part.trim_end_matches(|c| c == '\'' || c == 'h');
+                let index: u32 = digits.parse().map_err(|_| Error::InvalidDerivationPath)?;
+                if index >= HARDENED_BIT {
+                    return Err(Error::IndexOutOfRange);
+                }
+                path.push(if hardened {
+                    KeyIndex::Hardened(index)
+                } else {
+                    KeyIndex::Normal(index)
+                });
+            }
+            Ok(DerivationPath(path))
+        }
+    }
+
+    const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    fn sha256(data: &[u8]) -> Vec<u8> {
+        digest::digest(&digest::SHA256, data).as_ref().to_vec()
+    }
+
+    // RIPEMD160(SHA256(data)), the Hash160 used throughout Bitcoin for
+    // addresses and BIP32 key fingerprints.
+    fn hash160(data: &[u8]) -> [u8; 20] {
+        let mut out = [0u8; 20];
+        out.copy_from_slice(&Ripemd160::digest(&sha256(data)));
+        out
+    }
+
+    const P2PKH_VERSION_MAINNET: u8 = 0x00;
+    const P2PKH_VERSION_TESTNET: u8 = 0x6F;
+
+    // Base58Check(version || Hash160(serialized public key)), as used for
+    // pay-to-pubkey-hash receive addresses.
+    fn p2pkh_address(public_key: &PublicKey, network: Network, compressed: bool) -> String {
+        let serialized_public_key: Vec<u8> = if compressed {
+            public_key.serialize().to_vec()
+        } else {
+            public_key.serialize_uncompressed().to_vec()
+        };
+        let version = match network {
+            Network::Bitcoin => P2PKH_VERSION_MAINNET,
+            Network::Testnet => P2PKH_VERSION_TESTNET,
+        };
+        let mut payload = vec![version];
+        payload.extend_from_slice(&hash160(&serialized_public_key));
+        base58check_encode(&payload)
+    }
+
+    fn base58_encode(data: &[u8]) -> String {
+        let mut digits: Vec<u8> = vec![0];
+        for &input_byte in data {
+            let mut carry = input_byte as u32;
+            for digit in digits.iter_mut() {
+                carry += (*digit as u32) << 8;
+                *digit = (carry % 58) as u8;
+                carry /= 58;
+            }
+            while carry > 0 {
+                digits.push((carry % 58) as u8);
+                carry /= 58;
+            }
+        }
+        let leading_zeros = data.iter().take_while(|&&b| b == 0).count();
+        let mut result = String::with_capacity(leading_zeros + digits.len());
+        result.extend(std::iter::repeat('1').take(leading_zeros));
+        result.extend(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[d as usize] as char),
+        );
+        result
+    }
+
+    fn base58_decode(input: &str) -> Result<Vec<u8>, Error> {
+        let mut bytes: Vec<u8> = vec![0];
+        for c in input.chars() {
+            let value = BASE58_ALPHABET
+                .iter()
+                .position(|&b| b as char == c)
+                .ok_or(Error::InvalidBase58Character)? as u32;
+            let mut carry = value;
+            for byte in bytes.iter_mut() {
+                carry += (*byte as u32) * 58;
+                *byte = (carry & 0xff) as u8;
+                carry >>= 8;
+            }
+            while carry > 0 {
+                bytes.push((carry & 0xff) as u8);
+                carry >>= 8;
+            }
+        }
+        let leading_zeros = input.chars().take_while(|&c| c == '1').count();
+        let mut result = vec![0u8; leading_zeros];
+        result.extend(bytes.iter().rev());
+        Ok(result)
+    }
+
+    fn base58check_encode(payload: &[u8]) -> String {
+        let checksum = sha256(&sha256(payload));
+        let mut full = payload.to_vec();
+        full.extend_from_slice(&checksum[..4]);
+        base58_encode(&full)
+    }
+
+    fn base58check_decode(input: &str) -> Result<Vec<u8>, Error> {
+        let full = base58_decode(input)?;
+        if full.len() < 4 {
+            return Err(Error::InvalidBase58Checksum);
+        }
+        let (payload, checksum) = full.split_at(full.len() - 4);
+        let expected = sha256(&sha256(payload));
+        if &expected[..4] != checksum {
+            return Err(Error::InvalidBase58Checksum);
+        }
+        Ok(payload.to_vec())
+    }
+
     fn secp256k1_context() -> Secp256k1<secp256k1::All> {
         Secp256k1::new()
     }
@@ -76,85 +275,93 @@ mod tests {
         hmac::sign(&s_key, data)
     }
 
+    // Convenience wrapper around `ExtendedPrivKey::new_master` that fills an
+    // RNG-generated seed buffer first. Seed injection (`new_master`) is the
+    // primary API so wallets can derive deterministically from a recovery
+    // phrase; this is for callers who just want a fresh random master key.
     fn generate_master_key(seed_length: usize) -> Result<ExtendedPrivKey, Error> {
-        let seed = {
-            let mut seed = Vec::with_capacity(seed_length);
-            let mut rng = rand::thread_rng();
-            rng.fill(seed.as_mut_slice());
-            seed
-        };
-        let signature = hmac_sha512(b"Bitcoin seed", &seed);
-        let sig_bytes = signature.as_ref();
-        let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
-        if let Ok(private_key) = SecretKey::from_slice(key) {
-            return Ok(ExtendedPrivKey {
+        let mut seed = vec![0x00; seed_length];
+        rand::thread_rng().fill(seed.as_mut_slice());
+        ExtendedPrivKey::new_master(Network::Bitcoin, &seed)
+    }
+
+    impl ExtendedPrivKey {
+        /// Derives a master key from seed bytes, e.g. the 64-byte output of
+        /// a BIP39 mnemonic-to-seed step: `HMAC-SHA512("Bitcoin seed",
+        /// seed)`, split into key and chain code, at depth 0 with no
+        /// parent.
+        pub fn new_master(network: Network, seed: &[u8]) -> Result<ExtendedPrivKey, Error> {
+            let signature = hmac_sha512(b"Bitcoin seed", seed);
+            let sig_bytes = signature.as_ref();
+            let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
+            let private_key = SecretKey::from_slice(key).map_err(|_| Error::InvalidIndex)?;
+            Ok(ExtendedPrivKey {
                 private_key,
                 chain_code: chain_code.to_vec(),
-            });
+                network,
+                depth: 0,
+                parent_fingerprint: [0x00; 4],
+                child_number: 0,
+            })
         }
-        Err(Error::InvalidIndex)
-    }
 
-    fn to_hardened_key_index(index: u64) -> u64 {
-        if index < HARDENDED_KEY_START_INDEX {
-            HARDENDED_KEY_START_INDEX + index
-        } else {
-            index
+        /// Forwards to the matching public key's `identifier`.
+        pub fn identifier(&self) -> Result<[u8; 20], Error> {
+            Ok(ExtendedPubKey::from_private_key(self)?.identifier())
         }
-    }
 
-    impl ExtendedPrivKey {
-        fn derive_hardended_key(&self, index: u64) -> Result<ChildPrivKey, Error> {
-            let index = to_hardened_key_index(index);
-            if index > HARDENDED_KEY_END_INDEX {
-                return Err(Error::IndexOutOfRange);
-            }
+        /// Forwards to the matching public key's `fingerprint`.
+        pub fn fingerprint(&self) -> Result<[u8; 4], Error> {
+            Ok(ExtendedPubKey::from_private_key(self)?.fingerprint())
+        }
+
+        fn derive_hardended_key(&self, key_index: KeyIndex) -> Result<ChildPrivKey, Error> {
+            let raw_index = key_index.raw_index()?;
             let data = {
-                let mut data = Vec::with_capacity(33);
+                let mut data = Vec::with_capacity(37);
                 data.extend_from_slice(&[0x00]);
                 data.extend_from_slice(&self.private_key[..]);
-                let mut ser_index = [0u8; 32];
-                U256::from(index)
-                    .into_big_endian(&mut ser_index)
-                    .expect("big_endian encode");
-                data.extend_from_slice(&ser_index);
+                data.extend_from_slice(&raw_index.to_be_bytes());
                 data
             };
-            assert_eq!(data.len(), 65);
+            assert_eq!(data.len(), 37);
             let signature = hmac_sha512(&self.chain_code, &data);
             let sig_bytes = signature.as_ref();
             let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
-            if let Ok(private_key) = SecretKey::from_slice(key) {
+            if let Ok(mut private_key) = SecretKey::from_slice(key) {
+                private_key
+                    .add_assign(&self.private_key[..])
+                    .expect("add point");
                 return Ok(ChildPrivKey {
-                    index,
-                    key_mode: KeyMode::Hardened,
+                    key_index,
                     extended_key: ExtendedPrivKey {
                         private_key,
                         chain_code: chain_code.to_vec(),
+                        network: self.network,
+                        depth: self
+                            .depth
+                            .checked_add(1)
+                            .ok_or(Error::MaximumDepthExceeded)?,
+                        parent_fingerprint: self.fingerprint()?,
+                        child_number: raw_index,
                     },
                 });
             }
             Err(Error::InvalidIndex)
         }
 
-        fn derive_normal_key(&self, index: u64) -> Result<ChildPrivKey, Error> {
-            if index >= HARDENDED_KEY_START_INDEX {
-                return Err(Error::IndexOutOfRange);
-            }
+        fn derive_normal_key(&self, key_index: KeyIndex) -> Result<ChildPrivKey, Error> {
+            let raw_index = key_index.raw_index()?;
             let data = {
-                let mut data = Vec::with_capacity(33);
-                let secp = secp256k1_context();
+                let mut data = Vec::with_capacity(37);
                 let ser_public_key =
-                    PublicKey::from_secret_key(&secp, &self.private_key).serialize();
+                    PublicKey::from_secret_key(&SECP256K1_SIGNING_ONLY, &self.private_key)
+                        .serialize();
                 data.extend_from_slice(&ser_public_key[..]);
-                let mut ser_index = [0u8; 32];
-                U256::from(index)
-                    .into_big_endian(&mut ser_index)
-                    .expect("big_endian encode");
-                data.extend_from_slice(&ser_index);
+                data.extend_from_slice(&raw_index.to_be_bytes());
                 data
             };
-            assert_eq!(data.len(), 65);
+            assert_eq!(data.len(), 37);
             let signature = hmac_sha512(&self.chain_code, &data);
             let sig_bytes = signature.as_ref();
             let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
@@ -163,63 +370,165 @@ mod tests {
                     .add_assign(&self.private_key[..])
                     .expect("add point");
                 return Ok(ChildPrivKey {
-                    index,
-                    key_mode: KeyMode::Normal,
+                    key_index,
                     extended_key: ExtendedPrivKey {
                         private_key,
                         chain_code: chain_code.to_vec(),
+                        network: self.network,
+                        depth: self
+                            .depth
+                            .checked_add(1)
+                            .ok_or(Error::MaximumDepthExceeded)?,
+                        parent_fingerprint: self.fingerprint()?,
+                        child_number: raw_index,
                     },
                 });
             }
             Err(Error::InvalidIndex)
         }
 
-        pub fn derive_private_key(
-            &self,
-            key_mode: KeyMode,
-            index: u64,
-        ) -> Result<ChildPrivKey, Error> {
-            match key_mode {
-                KeyMode::Hardened => self.derive_hardended_key(index),
-                KeyMode::Normal => self.derive_normal_key(index),
+        pub fn derive_private_key(&self, key_index: KeyIndex) -> Result<ChildPrivKey, Error> {
+            match key_index {
+                KeyIndex::Hardened(_) => self.derive_hardended_key(key_index),
+                KeyIndex::Normal(_) => self.derive_normal_key(key_index),
+            }
+        }
+
+        /// Folds `derive_private_key` across every element of `path`,
+        /// returning the key at the end of the chain.
+        pub fn derive_priv(&self, path: &DerivationPath) -> Result<ExtendedPrivKey, Error> {
+            let mut key = self.clone();
+            for &key_index in &path.0 {
+                key = key.derive_private_key(key_index)?.extended_key;
+            }
+            Ok(key)
+        }
+
+        /// The 78-byte BIP32 payload: version || depth || parent fingerprint
+        /// || child number || chain code || 0x00 || private key.
+        pub fn serialize(&self) -> [u8; 78] {
+            let mut buf = [0u8; 78];
+            let version = match self.network {
+                Network::Bitcoin => VERSION_MAINNET_PRIVATE,
+                Network::Testnet => VERSION_TESTNET_PRIVATE,
+            };
+            buf[0..4].copy_from_slice(&version);
+            buf[4] = self.depth;
+            buf[5..9].copy_from_slice(&self.parent_fingerprint);
+            buf[9..13].copy_from_slice(&self.child_number.to_be_bytes());
+            buf[13..45].copy_from_slice(&self.chain_code);
+            buf[45] = 0x00;
+            buf[46..78].copy_from_slice(&self.private_key[..]);
+            buf
+        }
+    }
+
+    impl fmt::Display for ExtendedPrivKey {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&base58check_encode(&self.serialize()))
+        }
+    }
+
+    impl FromStr for ExtendedPrivKey {
+        type Err = Error;
+
+        fn from_str(s: &str) -> Result<Self, Error> {
+            let data = base58check_decode(s)?;
+            if data.len() != 78 {
+                return Err(Error::InvalidExtendedKeyLength);
+            }
+            let network = match &data[0..4] {
+                v if v == VERSION_MAINNET_PRIVATE => Network::Bitcoin,
+                v if v == VERSION_TESTNET_PRIVATE => Network::Testnet,
+                _ => return Err(Error::InvalidVersionBytes),
+            };
+            if data[45] != 0x00 {
+                return Err(Error::InvalidPrivateKeyPrefix);
             }
+            let depth = data[4];
+            let mut parent_fingerprint = [0u8; 4];
+            parent_fingerprint.copy_from_slice(&data[5..9]);
+            let mut child_number_bytes = [0u8; 4];
+            child_number_bytes.copy_from_slice(&data[9..13]);
+            let child_number = u32::from_be_bytes(child_number_bytes);
+            let chain_code = data[13..45].to_vec();
+            let private_key =
+                SecretKey::from_slice(&data[46..78]).map_err(|_| Error::InvalidIndex)?;
+            Ok(ExtendedPrivKey {
+                private_key,
+                chain_code,
+                network,
+                depth,
+                parent_fingerprint,
+                child_number,
+            })
         }
     }
 
     impl ExtendedPubKey {
-        fn derive_public_key(&self, index: u64) -> Result<ChildPubKey, Error> {
-            if index >= HARDENDED_KEY_START_INDEX {
-                return Err(Error::IndexOutOfRange);
+        /// The BIP32 key identifier: `Hash160` of the serialized compressed
+        /// public key.
+        pub fn identifier(&self) -> [u8; 20] {
+            hash160(&self.public_key.serialize())
+        }
+
+        /// The first 4 bytes of the identifier, embedded in child keys as
+        /// `parent_fingerprint`.
+        pub fn fingerprint(&self) -> [u8; 4] {
+            let mut fingerprint = [0u8; 4];
+            fingerprint.copy_from_slice(&self.identifier()[0..4]);
+            fingerprint
+        }
+
+        /// The Base58Check pay-to-pubkey-hash address for this key, on its
+        /// own network.
+        pub fn address(&self, compressed: bool) -> String {
+            p2pkh_address(&self.public_key, self.network, compressed)
+        }
+
+        /// Folds `derive_public_key` across every element of `path`. Fails
+        /// if `path` contains a hardened element, since hardened public
+        /// derivation is impossible.
+        pub fn derive_pub(&self, path: &DerivationPath) -> Result<ExtendedPubKey, Error> {
+            let mut key = self.clone();
+            for &key_index in &path.0 {
+                key = key.derive_public_key(key_index)?.extended_key;
             }
+            Ok(key)
+        }
+
+        fn derive_public_key(&self, key_index: KeyIndex) -> Result<ChildPubKey, Error> {
+            if let KeyIndex::Hardened(_) = key_index {
+                return Err(Error::CannotDeriveHardenedPublicKey);
+            }
+            let raw_index = key_index.raw_index()?;
             let data = {
-                let mut data = Vec::new();
+                let mut data = Vec::with_capacity(37);
                 data.extend_from_slice(&self.public_key.serialize());
-                let mut ser_index = [0u8; 32];
-                U256::from(index)
-                    .into_big_endian(&mut ser_index)
-                    .expect("big_endian encode");
-                data.extend_from_slice(&ser_index);
+                data.extend_from_slice(&raw_index.to_be_bytes());
                 data
             };
-            assert_eq!(data.len(), 65);
+            assert_eq!(data.len(), 37);
             let signature = hmac_sha512(&self.chain_code, &data);
             let sig_bytes = signature.as_ref();
             let (key, chain_code) = sig_bytes.split_at(sig_bytes.len() / 2);
-            println!(
-                "publickey : {:?}, key: {:?}",
-                PublicKey::from_slice(key.clone()),
-                key
-            );
             if let Ok(private_key) = SecretKey::from_slice(key) {
-                let secp = secp256k1_context();
                 let mut public_key = self.public_key.clone();
-                if let Ok(_) = public_key.add_exp_assign(&secp, &private_key[..]) {
+                if let Ok(_) =
+                    public_key.add_exp_assign(&SECP256K1_VERIFICATION_ONLY, &private_key[..])
+                {
                     return Ok(ChildPubKey {
-                        index,
-                        key_mode: KeyMode::Normal,
+                        key_index,
                         extended_key: ExtendedPubKey {
                             public_key,
                             chain_code: chain_code.to_vec(),
+                            network: self.network,
+                            depth: self
+                                .depth
+                                .checked_add(1)
+                                .ok_or(Error::MaximumDepthExceeded)?,
+                            parent_fingerprint: self.fingerprint(),
+                            child_number: raw_index,
                         },
                     });
                 }
@@ -228,11 +537,71 @@ mod tests {
         }
 
         pub fn from_private_key(extended_key: &ExtendedPrivKey) -> Result<Self, Error> {
-            let secp = secp256k1_context();
-            let public_key = PublicKey::from_secret_key(&secp, &extended_key.private_key);
+            let public_key =
+                PublicKey::from_secret_key(&SECP256K1_SIGNING_ONLY, &extended_key.private_key);
             Ok(ExtendedPubKey {
                 public_key,
                 chain_code: extended_key.chain_code.clone(),
+                network: extended_key.network,
+                depth: extended_key.depth,
+                parent_fingerprint: extended_key.parent_fingerprint,
+                child_number: extended_key.child_number,
+            })
+        }
+
+        /// The 78-byte BIP32 payload: version || depth || parent fingerprint
+        /// || child number || chain code || 33-byte compressed public key.
+        pub fn serialize(&self) -> [u8; 78] {
+            let mut buf = [0u8; 78];
+            let version = match self.network {
+                Network::Bitcoin => VERSION_MAINNET_PUBLIC,
+                Network::Testnet => VERSION_TESTNET_PUBLIC,
+            };
+            buf[0..4].copy_from_slice(&version);
+            buf[4] = self.depth;
+            buf[5..9].copy_from_slice(&self.parent_fingerprint);
+            buf[9..13].copy_from_slice(&self.child_number.to_be_bytes());
+            buf[13..45].copy_from_slice(&self.chain_code);
+            buf[45..78].copy_from_slice(&self.public_key.serialize());
+            buf
+        }
+    }
+
+    impl fmt::Display for ExtendedPubKey {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&base58check_encode(&self.serialize()))
+        }
+    }
+
+    impl FromStr for ExtendedPubKey {
+        type Err = Error;
+
+        fn from_str(s: &str) -> Result<Self, Error> {
+            let data = base58check_decode(s)?;
+            if data.len() != 78 {
+                return Err(Error::InvalidExtendedKeyLength);
+            }
+            let network = match &data[0..4] {
+                v if v == VERSION_MAINNET_PUBLIC => Network::Bitcoin,
+                v if v == VERSION_TESTNET_PUBLIC => Network::Testnet,
+                _ => return Err(Error::InvalidVersionBytes),
+            };
+            let depth = data[4];
+            let mut parent_fingerprint = [0u8; 4];
+            parent_fingerprint.copy_from_slice(&data[5..9]);
+            let mut child_number_bytes = [0u8; 4];
+            child_number_bytes.copy_from_slice(&data[9..13]);
+            let child_number = u32::from_be_bytes(child_number_bytes);
+            let chain_code = data[13..45].to_vec();
+            let public_key =
+                PublicKey::from_slice(&data[45..78]).map_err(|_| Error::InvalidIndex)?;
+            Ok(ExtendedPubKey {
+                public_key,
+                chain_code,
+                network,
+                depth,
+                parent_fingerprint,
+                child_number,
             })
         }
     }
@@ -241,8 +610,7 @@ mod tests {
         pub fn from_private_key(child_key: &ChildPrivKey) -> Result<Self, Error> {
             let extended_key = ExtendedPubKey::from_private_key(&child_key.extended_key)?;
             Ok(ChildPubKey {
-                index: child_key.index,
-                key_mode: child_key.key_mode,
+                key_index: child_key.key_index,
                 extended_key,
             })
         }
@@ -289,10 +657,10 @@ mod tests {
     fn derivation_private_child_key_from_private_parent_key() {
         let master_key = fetch_random_key(256);
         master_key
-            .derive_private_key(KeyMode::Hardened, 0)
+            .derive_private_key(KeyIndex::Hardened(0))
             .expect("hardended_key");
         master_key
-            .derive_private_key(KeyMode::Normal, 0)
+            .derive_private_key(KeyIndex::Normal(0))
             .expect("normal_key");
     }
 
@@ -300,16 +668,241 @@ mod tests {
     fn derivation_public_child_key_from_public_parent_key() {
         let master_key = fetch_random_key(256);
         let child_private_key = master_key
-            .derive_private_key(KeyMode::Normal, 0)
+            .derive_private_key(KeyIndex::Normal(0))
             .expect("hardended_key");
         let child_public_key =
             ChildPubKey::from_private_key(&child_private_key).expect("public key");
         let parent_public_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
         assert_eq!(
             parent_public_key
-                .derive_public_key(0)
+                .derive_public_key(KeyIndex::Normal(0))
                 .expect("derive public key"),
             child_public_key
         )
     }
+
+    #[test]
+    fn extended_priv_key_serialization_round_trip() {
+        let master_key = fetch_random_key(256);
+        let serialized = master_key.to_string();
+        let parsed = ExtendedPrivKey::from_str(&serialized).expect("parse xprv");
+        assert_eq!(master_key, parsed);
+    }
+
+    #[test]
+    fn extended_pub_key_serialization_round_trip() {
+        let master_key = fetch_random_key(256);
+        let public_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
+        let serialized = public_key.to_string();
+        let parsed = ExtendedPubKey::from_str(&serialized).expect("parse xpub");
+        assert_eq!(public_key, parsed);
+    }
+
+    #[test]
+    fn base58check_rejects_corrupted_checksum() {
+        let master_key = fetch_random_key(256);
+        let mut serialized = master_key.to_string();
+        serialized.push('1');
+        assert_eq!(
+            ExtendedPrivKey::from_str(&serialized),
+            Err(Error::InvalidBase58Checksum)
+        );
+    }
+
+    #[test]
+    fn fingerprint_is_first_four_bytes_of_identifier() {
+        let master_key = fetch_random_key(256);
+        let public_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
+        assert_eq!(
+            &public_key.fingerprint()[..],
+            &public_key.identifier()[0..4]
+        );
+        assert_eq!(
+            master_key.fingerprint().expect("fingerprint"),
+            public_key.fingerprint()
+        );
+    }
+
+    #[test]
+    fn child_key_embeds_parent_fingerprint() {
+        let master_key = fetch_random_key(256);
+        let child_private_key = master_key
+            .derive_private_key(KeyIndex::Hardened(0))
+            .expect("hardened_key");
+        assert_eq!(
+            child_private_key.extended_key.parent_fingerprint,
+            master_key.fingerprint().expect("fingerprint")
+        );
+    }
+
+    #[test]
+    fn derivation_path_parses_hardened_and_normal_levels() {
+        let path: DerivationPath = "m/44'/0'/0'/0/5".parse().expect("parse path");
+        assert_eq!(
+            path,
+            DerivationPath(vec![
+                KeyIndex::Hardened(44),
+                KeyIndex::Hardened(0),
+                KeyIndex::Hardened(0),
+                KeyIndex::Normal(0),
+                KeyIndex::Normal(5),
+            ])
+        );
+    }
+
+    #[test]
+    fn derivation_path_rejects_missing_master_prefix() {
+        assert_eq!(
+            "44'/0'/0'".parse::<DerivationPath>(),
+            Err(Error::InvalidDerivationPath)
+        );
+    }
+
+    #[test]
+    fn derive_priv_matches_one_level_at_a_time_derivation() {
+        let master_key = fetch_random_key(256);
+        let path: DerivationPath = "m/0'/0".parse().expect("parse path");
+        let derived = master_key.derive_priv(&path).expect("derive path");
+        let expected = master_key
+            .derive_private_key(KeyIndex::Hardened(0))
+            .expect("hardened child")
+            .extended_key
+            .derive_private_key(KeyIndex::Normal(0))
+            .expect("normal grandchild")
+            .extended_key;
+        assert_eq!(derived, expected);
+    }
+
+    #[test]
+    fn derive_pub_rejects_hardened_path_elements() {
+        let master_key = fetch_random_key(256);
+        let public_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
+        let path: DerivationPath = "m/0'".parse().expect("parse path");
+        assert_eq!(
+            public_key.derive_pub(&path),
+            Err(Error::CannotDeriveHardenedPublicKey)
+        );
+    }
+
+    #[test]
+    fn key_index_raw_index_adds_hardened_offset() {
+        assert_eq!(KeyIndex::Normal(5).raw_index(), Ok(5));
+        assert_eq!(KeyIndex::Hardened(5).raw_index(), Ok(HARDENED_BIT + 5));
+    }
+
+    #[test]
+    fn key_index_raw_index_rejects_normal_index_with_hardened_bit_set() {
+        assert_eq!(
+            KeyIndex::Normal(HARDENED_BIT).raw_index(),
+            Err(Error::IndexOutOfRange)
+        );
+        assert_eq!(
+            KeyIndex::Normal(HARDENED_BIT + 5).raw_index(),
+            Err(Error::IndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn key_index_normalize_index_strips_hardened_offset() {
+        assert_eq!(KeyIndex::Normal(5).normalize_index(), 5);
+        assert_eq!(KeyIndex::Hardened(5).normalize_index(), 5);
+    }
+
+    #[test]
+    fn key_index_from_index_round_trips_raw_index() {
+        assert_eq!(KeyIndex::from_index(5), Ok(KeyIndex::Normal(5)));
+        assert_eq!(
+            KeyIndex::from_index(HARDENED_BIT + 5),
+            Ok(KeyIndex::Hardened(5))
+        );
+    }
+
+    #[test]
+    fn address_decodes_to_hash160_of_public_key() {
+        let master_key = fetch_random_key(256);
+        let public_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
+        let address = public_key.address(true);
+        let decoded = base58check_decode(&address).expect("decode address");
+        assert_eq!(decoded[0], P2PKH_VERSION_MAINNET);
+        assert_eq!(&decoded[1..], &hash160(&public_key.public_key.serialize()));
+    }
+
+    #[test]
+    fn address_differs_by_network_and_compression() {
+        let master_key = fetch_random_key(256);
+        let mainnet_key = ExtendedPubKey::from_private_key(&master_key).expect("public key");
+        let mut testnet_key = mainnet_key.clone();
+        testnet_key.network = Network::Testnet;
+        assert_ne!(mainnet_key.address(true), testnet_key.address(true));
+        assert_ne!(mainnet_key.address(true), mainnet_key.address(false));
+    }
+
+    #[test]
+    fn cached_contexts_agree_with_a_fresh_full_context() {
+        let secp = secp256k1_context();
+        let secret_key = random_secret_key();
+        let via_signing_only = PublicKey::from_secret_key(&SECP256K1_SIGNING_ONLY, &secret_key);
+        let via_full_context = PublicKey::from_secret_key(&secp, &secret_key);
+        assert_eq!(via_signing_only, via_full_context);
+    }
+
+    #[test]
+    fn new_master_is_deterministic_given_the_same_seed() {
+        let seed = [0x2a; 64];
+        let a = ExtendedPrivKey::new_master(Network::Bitcoin, &seed).expect("master key");
+        let b = ExtendedPrivKey::new_master(Network::Bitcoin, &seed).expect("master key");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn new_master_differs_from_a_master_key_over_an_empty_seed() {
+        let master_key =
+            ExtendedPrivKey::new_master(Network::Bitcoin, &[0x2a; 64]).expect("master key");
+        let empty_seed_key =
+            ExtendedPrivKey::new_master(Network::Bitcoin, &[]).expect("master key");
+        assert_ne!(master_key, empty_seed_key);
+    }
+
+    #[test]
+    fn generate_master_key_does_not_hash_an_empty_seed() {
+        let a = generate_master_key(64).expect("master key");
+        let b = generate_master_key(64).expect("master key");
+        assert_ne!(a, b);
+    }
+
+    fn to_hex(data: &[u8]) -> String {
+        data.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    // BIP32 test vector 1, see
+    // https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vectors
+    #[test]
+    fn derivation_matches_bip32_test_vector_1() {
+        let seed = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let master_key = ExtendedPrivKey::new_master(Network::Bitcoin, &seed).expect("master key");
+        assert_eq!(
+            to_hex(&master_key.private_key[..]),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+        );
+        assert_eq!(
+            to_hex(&master_key.chain_code),
+            "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508",
+        );
+
+        let child_key = master_key
+            .derive_private_key(KeyIndex::Hardened(0))
+            .expect("m/0' child")
+            .extended_key;
+        assert_eq!(
+            to_hex(&child_key.private_key[..]),
+            "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea",
+        );
+        assert_eq!(
+            to_hex(&child_key.chain_code),
+            "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141",
+        );
+    }
 }